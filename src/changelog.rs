@@ -0,0 +1,164 @@
+use std::fmt::Write as _;
+
+use crate::conventional_commit::{ConventionalCommit, ConventionalCommitType};
+
+/// Section a commit's changelog entry is grouped under, also the order
+/// sections are rendered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Breaking,
+    Features,
+    BugFixes,
+    Performance,
+}
+
+impl Section {
+    const ALL: [Section; 4] = [
+        Section::Breaking,
+        Section::Features,
+        Section::BugFixes,
+        Section::Performance,
+    ];
+
+    fn heading(self) -> &'static str {
+        match self {
+            Section::Breaking => "Breaking Changes",
+            Section::Features => "Features",
+            Section::BugFixes => "Bug Fixes",
+            Section::Performance => "Performance",
+        }
+    }
+
+    fn for_commit(commit: &ConventionalCommit) -> Option<Self> {
+        if commit.is_breaking_change {
+            return Some(Section::Breaking);
+        }
+
+        match ConventionalCommitType::from(commit) {
+            ConventionalCommitType::Feat => Some(Section::Features),
+            ConventionalCommitType::Fix => Some(Section::BugFixes),
+            ConventionalCommitType::Perf => Some(Section::Performance),
+            _ => None,
+        }
+    }
+}
+
+/// Renders a Markdown changelog from the conventional commits in a release
+/// range, grouped into sections by type. `authors` must be the git commit
+/// each entry in `commits` was parsed from, in the same order, so each
+/// entry can be attributed via `git2::Commit::author`.
+pub fn render(commits: &[ConventionalCommit], authors: &[git2::Commit]) -> String {
+    let mut changelog = String::new();
+
+    for section in Section::ALL {
+        let entries = commits
+            .iter()
+            .zip(authors)
+            .filter(|(commit, _)| Section::for_commit(commit) == Some(section));
+
+        let mut entries = entries.peekable();
+        if entries.peek().is_none() {
+            continue;
+        }
+
+        let _ = writeln!(changelog, "## {}\n", section.heading());
+        for (commit, author) in entries {
+            let scope = commit
+                .scope
+                .map(|scope| format!("**{scope}:** "))
+                .unwrap_or_default();
+
+            let _ = writeln!(
+                changelog,
+                "- {scope}{} ({})",
+                commit.desc,
+                author.author().name().unwrap_or("unknown")
+            );
+        }
+        changelog.push('\n');
+    }
+
+    changelog
+}
+
+#[cfg(test)]
+mod tests {
+    use git2::Repository;
+
+    use super::*;
+
+    /// Inits a repo in a tempdir with a single empty commit authored by
+    /// `name`, returning the tempdir (kept alive to keep the repo on disk),
+    /// the repo, and the commit's id.
+    fn commit_authored_by(name: &str) -> (tempfile::TempDir, Repository, git2::Oid) {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now(name, "author@example.com").unwrap();
+        let oid = repo
+            .commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        drop(tree);
+
+        (dir, repo, oid)
+    }
+
+    fn commit(
+        ty: &'static str,
+        desc: &'static str,
+        scope: Option<&'static str>,
+        is_breaking_change: bool,
+    ) -> ConventionalCommit<'static> {
+        ConventionalCommit {
+            ty,
+            body: None,
+            desc,
+            footer: vec![],
+            is_breaking_change,
+            scope,
+        }
+    }
+
+    #[test]
+    fn test_render_groups_commits_into_sections_with_headings() {
+        let (_dir, repo, oid) = commit_authored_by("Alice");
+
+        let commits = vec![
+            commit("feat", "add widget", None, false),
+            commit("fix", "fix widget", Some("core"), false),
+        ];
+        let authors = vec![repo.find_commit(oid).unwrap(), repo.find_commit(oid).unwrap()];
+
+        let rendered = render(&commits, &authors);
+
+        assert!(rendered.contains("## Features"));
+        assert!(rendered.contains("- add widget (Alice)"));
+        assert!(rendered.contains("## Bug Fixes"));
+        assert!(rendered.contains("- **core:** fix widget (Alice)"));
+        assert!(!rendered.contains("## Performance"));
+        assert!(!rendered.contains("## Breaking Changes"));
+    }
+
+    #[test]
+    fn test_render_skips_sections_with_no_matching_commits() {
+        let (_dir, repo, oid) = commit_authored_by("Bob");
+        let commits = vec![commit("chore", "bump deps", None, false)];
+        let authors = vec![repo.find_commit(oid).unwrap()];
+
+        assert_eq!(render(&commits, &authors), "");
+    }
+
+    #[test]
+    fn test_render_breaking_change_only_appears_under_breaking_section() {
+        let (_dir, repo, oid) = commit_authored_by("Carol");
+        let commits = vec![commit("feat", "rework api", None, true)];
+        let authors = vec![repo.find_commit(oid).unwrap()];
+
+        let rendered = render(&commits, &authors);
+
+        assert!(rendered.contains("## Breaking Changes"));
+        assert!(rendered.contains("- rework api (Carol)"));
+        assert_eq!(rendered.matches("##").count(), 1);
+    }
+}
@@ -0,0 +1,103 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::conventional_commit::ConventionalCommitType;
+
+/// Strength of a version bump, ordered so that the strongest required bump
+/// over a set of commits can be picked with a plain `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BumpLevel {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("TODO")]
+    Io(#[from] std::io::Error),
+    #[error("TODO")]
+    TomlParse(#[from] toml::de::Error),
+}
+
+/// Maps conventional commit types to the bump level they trigger. A type
+/// absent from the table falls back to the conventional `feat` -> minor /
+/// `fix` -> patch mapping, so an empty table behaves exactly like no
+/// config being present at all.
+#[derive(Debug, Clone, Default)]
+pub struct BumpLevelTable(HashMap<ConventionalCommitType, BumpLevel>);
+
+impl BumpLevelTable {
+    /// Loads `[package.metadata.semver-bump]` from `manifest` if present,
+    /// otherwise falls back to a dedicated `semver-bump.toml` in `workdir`,
+    /// otherwise the conventional mapping.
+    pub fn load(workdir: &Path, manifest: &cargo_toml::Manifest) -> Result<Self, ConfigError> {
+        if let Some(table) = manifest
+            .package()
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get("semver-bump"))
+        {
+            return Self::from_toml_value(table.clone());
+        }
+
+        let dedicated_path = workdir.join("semver-bump.toml");
+        if dedicated_path.exists() {
+            return Self::from_toml_value(fs::read_to_string(dedicated_path)?.parse()?);
+        }
+
+        Ok(Self::default())
+    }
+
+    pub(crate) fn from_toml_value(value: toml::Value) -> Result<Self, ConfigError> {
+        let raw: HashMap<String, BumpLevel> = value.try_into()?;
+        Ok(Self(
+            raw.into_iter()
+                .map(|(ty, level)| (ConventionalCommitType::new(&ty), level))
+                .collect(),
+        ))
+    }
+
+    pub fn level_for(&self, type_: &ConventionalCommitType) -> BumpLevel {
+        self.0.get(type_).copied().unwrap_or_else(|| match type_ {
+            ConventionalCommitType::Feat => BumpLevel::Minor,
+            ConventionalCommitType::Fix => BumpLevel::Patch,
+            _ => BumpLevel::None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_table_matches_conventional_mapping() {
+        let table = BumpLevelTable::default();
+        assert_eq!(table.level_for(&ConventionalCommitType::Feat), BumpLevel::Minor);
+        assert_eq!(table.level_for(&ConventionalCommitType::Fix), BumpLevel::Patch);
+        assert_eq!(table.level_for(&ConventionalCommitType::Chore), BumpLevel::None);
+    }
+
+    #[test]
+    fn test_custom_type_can_be_configured() {
+        let table = BumpLevelTable::from_toml_value(
+            "perf = \"minor\"\ndeps = \"none\"".parse().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            table.level_for(&ConventionalCommitType::Perf),
+            BumpLevel::Minor
+        );
+        assert_eq!(
+            table.level_for(&ConventionalCommitType::Custom("deps".to_owned())),
+            BumpLevel::None
+        );
+        // Unconfigured custom scopes still fall back to the conventional default.
+        assert_eq!(
+            table.level_for(&ConventionalCommitType::Custom("security".to_owned())),
+            BumpLevel::None
+        );
+    }
+}
@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 pub use conventional_commits_parser::Commit as ConventionalCommit;
 
-#[derive(Debug, PartialEq, Eq, Clone, strum_macros::EnumString)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, strum_macros::EnumString)]
 #[strum(serialize_all = "snake_case")]
 pub enum ConventionalCommitType {
     Build,
@@ -1,16 +1,29 @@
-use std::{fs, io, ops::Not, str};
+use std::{fmt::Write as _, fs, io, ops::Not, path::Path, str};
 
 use cargo_toml::{Inheritable, Manifest};
-use git2::Repository;
+use conventional_commit::ConventionalCommit;
+use git2::{Commit, Repository};
 use log::*;
 use semver::Version;
 use some_to_err::ErrOr;
 
+mod changelog;
+mod config;
 mod conventional_commit;
+mod tags;
 mod version_update_handler;
 
+use config::BumpLevelTable;
 use version_update_handler::{ProcessResult, VersionUpdateHandler, VersionUpdateTooWeak};
 
+/// First `--name value` pair found among the process's CLI arguments.
+fn flag_value(name: &str) -> Option<String> {
+    std::env::args()
+        .collect::<Vec<_>>()
+        .windows(2)
+        .find_map(|window| (window[0] == name).then(|| window[1].clone()))
+}
+
 #[derive(Debug, thiserror::Error)]
 enum Error {
     #[error("TODO")]
@@ -37,6 +50,335 @@ enum Error {
     CommitNotConvential(String),
     #[error("TODO")]
     LostVersionAtCargoToml,
+    #[error("TODO")]
+    Config(#[from] config::ConfigError),
+    #[error("TODO")]
+    WhileWriteChangelog(io::Error),
+    #[error("TODO")]
+    Tag(#[from] tags::TagError),
+}
+
+/// Version recorded in `Cargo.toml` as it was committed in `commit`. `workspace_root`
+/// resolves `version.workspace = true` entries against the root manifest's shared
+/// `[workspace.package]` defaults - needed for any member manifest that inherits
+/// its version, since parsing a historical blob doesn't do that on its own.
+fn cargo_toml_version_at_commit(
+    repo: &Repository,
+    commit: &Commit,
+    workdir: &Path,
+    cargo_toml_path_relative: &Path,
+    workspace_root: Option<&Manifest>,
+) -> Result<Version, Error> {
+    let blob = repo.find_blob(commit.tree()?.get_path(cargo_toml_path_relative)?.id())?;
+    let content = str::from_utf8(blob.content())?;
+    let mut manifest = Manifest::from_str(content)?;
+    manifest.complete_from_path_and_workspace(
+        &workdir.join(cargo_toml_path_relative),
+        workspace_root.map(|root| (root, workdir)),
+    )?;
+    Ok(Version::parse(manifest.package().version())?)
+}
+
+/// Walks history from `head` back to the baseline - the last commit that
+/// touched the version field - returning the version the baseline commit
+/// set and every commit seen before reaching it (baseline excluded, so
+/// none when `head` itself touched the version field), in newest-first order.
+fn commits_since_baseline<'repo>(
+    repo: &'repo Repository,
+    head: &Commit<'repo>,
+    workdir: &Path,
+    cargo_toml_path_relative: &Path,
+) -> Result<(Version, Vec<Commit<'repo>>), Error> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(head.id())?;
+
+    let mut commits = Vec::new();
+
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let commit_version =
+            cargo_toml_version_at_commit(repo, &commit, workdir, cargo_toml_path_relative, None)?;
+
+        let parent_version = match commit.parent(0) {
+            Ok(parent) => Some(cargo_toml_version_at_commit(
+                repo,
+                &parent,
+                workdir,
+                cargo_toml_path_relative,
+                None,
+            )?),
+            Err(_) => None,
+        };
+
+        if parent_version.as_ref() != Some(&commit_version) {
+            trace!("Baseline commit {}: version {commit_version}", commit.id());
+            return Ok((commit_version, commits));
+        }
+
+        trace!("Commit {} is part of the release range", commit.id());
+        commits.push(commit);
+    }
+
+    Err(Error::LostVersionAtCargoToml)
+}
+
+/// Every commit reachable from `head` but not from `baseline`, newest-first.
+fn commits_since_commit<'repo>(
+    repo: &'repo Repository,
+    head: &Commit<'repo>,
+    baseline: &Commit<'repo>,
+) -> Result<Vec<Commit<'repo>>, Error> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(head.id())?;
+    revwalk.hide(baseline.id())?;
+
+    revwalk.map(|oid| Ok(repo.find_commit(oid?)?)).collect()
+}
+
+/// The very first commit reachable from `head`. Used as the workspace
+/// baseline when no release tag exists yet: a virtual workspace manifest
+/// has no single version field to diff against, unlike a package manifest.
+fn root_commit<'repo>(repo: &'repo Repository, head: &Commit<'repo>) -> Result<Commit<'repo>, Error> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(head.id())?;
+    let oid = revwalk.last().ok_or(Error::LostVersionAtCargoToml)??;
+    Ok(repo.find_commit(oid)?)
+}
+
+fn parse_commits<'repo>(commits: &'repo [Commit<'repo>]) -> Result<Vec<ConventionalCommit<'repo>>, Error> {
+    commits
+        .iter()
+        .map(|commit| {
+            conventional_commits_parser::parse_commit_msg(
+                commit.message().ok_or(Error::CommitMessageEmpty)?,
+            )
+            .map_err(|err| Error::CommitNotConvential(format!("{err:?}")))
+        })
+        .collect()
+}
+
+/// A workspace member: its conventional-commit scope, inferred from its
+/// directory name, and the path to its crate root.
+struct WorkspaceMember {
+    scope: String,
+    path: std::path::PathBuf,
+}
+
+/// Expands `[workspace].members` entries - literal paths and `dir/*` globs
+/// - into concrete member directories, using each directory's name as its
+/// conventional-commit scope.
+fn workspace_members(
+    workdir: &Path,
+    workspace: &cargo_toml::Workspace,
+) -> Result<Vec<WorkspaceMember>, Error> {
+    let mut members = Vec::new();
+
+    for pattern in &workspace.members {
+        let dirs = match pattern.strip_suffix("/*") {
+            Some(prefix) => fs::read_dir(workdir.join(prefix))?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<Result<Vec<_>, Error>>()?
+                .into_iter()
+                .filter(|path| path.is_dir())
+                .collect(),
+            None => vec![workdir.join(pattern)],
+        };
+
+        for path in dirs {
+            let scope = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .ok_or(Error::LostCargoToml)?
+                .to_owned();
+            members.push(WorkspaceMember { scope, path });
+        }
+    }
+
+    Ok(members)
+}
+
+/// Bumps every workspace member independently: a commit only affects a
+/// member when the commit's scope matches that member's directory name.
+/// All member manifest writes are folded into a single commit amend.
+fn run_workspace(
+    repo: &Repository,
+    workdir: &Path,
+    head: &Commit,
+    root_manifest: &Manifest,
+    tag_prefix: &str,
+    pre_release: Option<&str>,
+) -> Result<(), Error> {
+    let workspace = root_manifest
+        .workspace
+        .as_ref()
+        .expect("run_workspace is only called when root_manifest.workspace is Some");
+    let members = workspace_members(workdir, workspace)?;
+
+    let mut index = repo.index()?;
+    let mut pending_writes = Vec::new();
+    let mut bumped_members = Vec::new();
+
+    for member in &members {
+        let member_cargo_toml_path = member.path.join("Cargo.toml");
+        if !member_cargo_toml_path.exists() {
+            warn!("Workspace member {} has no Cargo.toml, skipping", member.scope);
+            continue;
+        }
+        let member_cargo_toml_relative = member_cargo_toml_path
+            .strip_prefix(workdir)
+            .expect("Safe, this is relatie path")
+            .to_owned();
+
+        // Each member is tagged independently (`<scope>-<tag_prefix><version>`),
+        // so its baseline has to be looked up under that same scoped prefix -
+        // not the plain repo-wide prefix, which no per-member tag ever matches.
+        let member_tag_prefix = format!("{}-{tag_prefix}", member.scope);
+        let baseline_commit = match tags::latest_tagged_version(repo, &member_tag_prefix)? {
+            Some((_, oid)) => repo.find_object(oid, None)?.peel_to_commit()?,
+            None => root_commit(repo, head)?,
+        };
+        let range_commits = commits_since_commit(repo, head, &baseline_commit)?;
+
+        let previous = cargo_toml_version_at_commit(
+            repo,
+            &baseline_commit,
+            workdir,
+            &member_cargo_toml_relative,
+            Some(root_manifest),
+        )?;
+        let mut member_manifest = Manifest::from_path(&member_cargo_toml_path)?;
+        member_manifest.complete_from_path_and_workspace(
+            &member_cargo_toml_path,
+            Some((root_manifest, workdir)),
+        )?;
+        let current = Version::parse(member_manifest.package().version())?;
+
+        let scoped_commits = range_commits
+            .iter()
+            .filter_map(|commit| {
+                let parsed =
+                    conventional_commits_parser::parse_commit_msg(commit.message()?).ok()?;
+                (parsed.scope == Some(member.scope.as_str())).then_some(parsed)
+            })
+            .collect();
+
+        let bump_levels = BumpLevelTable::load(workdir, &member_manifest)?;
+        let ctx = VersionUpdateHandler {
+            current,
+            previous,
+            commits: scoped_commits,
+            bump_levels,
+            pre_release: pre_release.map(str::to_owned),
+        };
+
+        match ctx.get_next_version() {
+            Ok(ProcessResult::Patch { new }) => {
+                member_manifest
+                    .package
+                    .as_mut()
+                    .ok_or(Error::LostVersionAtCargoToml)?
+                    .version = Inheritable::from(Some(new.to_string()));
+                member_manifest.bin.clear();
+
+                let manifest_new_content = toml::to_string_pretty(&member_manifest)?;
+
+                let member_cargo_toml_entry = index
+                    .get_path(&member_cargo_toml_relative, 0)
+                    .ok_or(Error::LostCargoToml)?;
+                index.add_frombuffer(&member_cargo_toml_entry, manifest_new_content.as_bytes())?;
+
+                println!("Patched {} to {new}", member.scope);
+                bumped_members.push((member.scope.clone(), new));
+                pending_writes.push((
+                    member_cargo_toml_path,
+                    member_cargo_toml_relative,
+                    manifest_new_content,
+                ));
+            }
+            Ok(ProcessResult::ManualChanged { previous, current }) => println!(
+                "Issue an INFO that {} has been changed manually and respects versioning rules: Previous: {previous}, Current: {current}",
+                member.scope
+            ),
+            Err(VersionUpdateTooWeak {
+                expected_at_least,
+                actual,
+            }) => eprintln!(
+                "Issue a WARN that {} has been changed manually and does NOT comply with versioning rules: Actual: {actual}, Expected: >={expected_at_least}",
+                member.scope
+            ),
+        }
+    }
+
+    if bumped_members.is_empty() {
+        return Ok(());
+    }
+
+    // TODO Add signature, if it was presented early
+    let new_commit_oid = head.amend(
+        Some("HEAD"),
+        None,
+        None,
+        None,
+        None,
+        Some(&repo.find_tree(index.write_tree()?)?),
+    )?;
+
+    for (path, relative, content) in pending_writes {
+        // TODO Modify only version, not full file
+        fs::write(path, content)?;
+        index.add_path(&relative)?;
+    }
+    index.write()?;
+
+    let new_commit = repo.find_commit(new_commit_oid)?;
+    for (scope, version) in bumped_members {
+        tags::create_release_tag(repo, &format!("{scope}-{tag_prefix}"), &version, &new_commit)?;
+    }
+
+    Ok(())
+}
+
+/// Renders a changelog per workspace member, each scoped to the commits
+/// since that member's own last release tag - mirroring the scoped
+/// baselines `run_workspace` bumps against, rather than a single
+/// repo-wide baseline no per-member tag would ever match.
+fn workspace_changelog(
+    repo: &Repository,
+    workdir: &Path,
+    head: &Commit,
+    workspace: &cargo_toml::Workspace,
+    tag_prefix: &str,
+) -> Result<String, Error> {
+    let members = workspace_members(workdir, workspace)?;
+    let mut changelog = String::new();
+
+    for member in &members {
+        let member_tag_prefix = format!("{}-{tag_prefix}", member.scope);
+        let baseline_commit = match tags::latest_tagged_version(repo, &member_tag_prefix)? {
+            Some((_, oid)) => repo.find_object(oid, None)?.peel_to_commit()?,
+            None => root_commit(repo, head)?,
+        };
+
+        let range_commits: Vec<Commit> = commits_since_commit(repo, head, &baseline_commit)?
+            .into_iter()
+            .filter(|commit| {
+                commit
+                    .message()
+                    .and_then(|message| conventional_commits_parser::parse_commit_msg(message).ok())
+                    .is_some_and(|parsed| parsed.scope == Some(member.scope.as_str()))
+            })
+            .collect();
+
+        if range_commits.is_empty() {
+            continue;
+        }
+
+        let commits = parse_commits(&range_commits)?;
+        let _ = writeln!(changelog, "# {}\n", member.scope);
+        changelog.push_str(&changelog::render(&commits, &range_commits));
+    }
+
+    Ok(changelog)
 }
 
 fn main() -> Result<(), Error> {
@@ -58,31 +400,68 @@ fn main() -> Result<(), Error> {
         .strip_prefix(workdir)
         .expect("Safe, this is relatie path");
 
-    let parent_cargo_toml_blob = repo.find_blob(
-        commit
-            .parent(0)?
-            .tree()?
-            .get_path(cargo_toml_path_relative)?
-            .id(),
-    )?;
+    let root_manifest = Manifest::from_path(&cargo_toml_path)?;
 
-    let parent_cargo_toml_str = str::from_utf8(parent_cargo_toml_blob.content())?;
-    let previous_commit_manifest_version = Version::parse(
-        Manifest::from_str(parent_cargo_toml_str)?
-            .package()
-            .version(),
-    )?;
+    let tag_prefix = flag_value("--tag-prefix").unwrap_or_else(|| "v".to_owned());
+    let pre_release = flag_value("--pre-release");
 
-    let mut manifest = Manifest::from_path(&cargo_toml_path)?;
+    // Checked before the workspace branch so `changelog` is honored for
+    // workspaces too, instead of falling through into a real version bump.
+    if std::env::args().nth(1).as_deref() == Some("changelog") {
+        let changelog = if let Some(workspace) = &root_manifest.workspace {
+            workspace_changelog(&repo, workdir, &commit, workspace, &tag_prefix)?
+        } else {
+            let baseline_commit = match tags::latest_tagged_version(&repo, &tag_prefix)? {
+                Some((_, oid)) => repo.find_object(oid, None)?.peel_to_commit()?,
+                None => root_commit(&repo, &commit)?,
+            };
+            let range_commits = commits_since_commit(&repo, &commit, &baseline_commit)?;
+            let commits = parse_commits(&range_commits)?;
+
+            changelog::render(&commits, &range_commits)
+        };
+
+        match std::env::args().nth(2) {
+            Some(path) => fs::write(path, changelog).map_err(Error::WhileWriteChangelog)?,
+            None => print!("{changelog}"),
+        }
+        return Ok(());
+    }
+
+    if root_manifest.workspace.is_some() {
+        return run_workspace(
+            &repo,
+            workdir,
+            &commit,
+            &root_manifest,
+            &tag_prefix,
+            pre_release.as_deref(),
+        );
+    }
+
+    let (previous_commit_manifest_version, range_commits) =
+        match tags::latest_tagged_version(&repo, &tag_prefix)? {
+            Some((version, oid)) => {
+                let baseline_commit = repo.find_object(oid, None)?.peel_to_commit()?;
+                (version, commits_since_commit(&repo, &commit, &baseline_commit)?)
+            }
+            // No release tag yet: fall back to the last commit that touched
+            // the version field, starting from the manifest version there.
+            None => commits_since_baseline(&repo, &commit, workdir, cargo_toml_path_relative)?,
+        };
+
+    let commits = parse_commits(&range_commits)?;
+
+    let mut manifest = root_manifest;
     let current = Version::parse(manifest.package().version())?;
+    let bump_levels = BumpLevelTable::load(workdir, &manifest)?;
 
     let ctx = VersionUpdateHandler {
         current,
         previous: previous_commit_manifest_version,
-        commit: conventional_commits_parser::parse_commit_msg(
-            commit.message().ok_or(Error::CommitMessageEmpty)?,
-        )
-        .map_err(|err| Error::CommitNotConvential(format!("{err:?}")))?,
+        commits,
+        bump_levels,
+        pre_release,
     };
 
     match ctx.get_next_version() {
@@ -96,7 +475,7 @@ fn main() -> Result<(), Error> {
             let cargo_toml_entry = index.get_path(cargo_toml_path_relative, 0).ok_or(Error::LostCargoToml)?;
             index.add_frombuffer(&cargo_toml_entry, manifest_new_content.as_bytes())?;
             // TODO Add signature, if it was presented early
-            commit.amend(Some("HEAD"), None, None, None, None, Some(&repo.find_tree(index.write_tree()?)?))?;
+            let new_commit_oid = commit.amend(Some("HEAD"), None, None, None, None, Some(&repo.find_tree(index.write_tree()?)?))?;
 
             // TODO Modify only version, not full file
             fs::write(&cargo_toml_path, manifest_new_content)?;
@@ -104,6 +483,9 @@ fn main() -> Result<(), Error> {
             index.add_path(cargo_toml_path_relative)?;
             index.write()?;
 
+            let new_commit = repo.find_commit(new_commit_oid)?;
+            tags::create_release_tag(&repo, &tag_prefix, &new, &new_commit)?;
+
             println!("Patched");
         }
         Ok(ProcessResult::ManualChanged { previous, current }) => println!("Issue an INFO that the version has been changed manually and respects versioning rules: Previous: {previous}, Current: {current}"),
@@ -0,0 +1,113 @@
+use git2::{Oid, Repository};
+use semver::Version;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TagError {
+    #[error("TODO")]
+    Git(#[from] git2::Error),
+}
+
+/// Scans every tag in the repository, strips `prefix` from its name, and
+/// parses what remains as a semver version. Tags that aren't `<prefix><semver>`
+/// are ignored. Returns the greatest version found and the object it
+/// points at (a commit, or an annotated tag object peeling to one).
+pub fn latest_tagged_version(
+    repo: &Repository,
+    prefix: &str,
+) -> Result<Option<(Version, Oid)>, TagError> {
+    let mut tags = Vec::new();
+
+    repo.tag_foreach(|oid, name| {
+        let name = String::from_utf8_lossy(name);
+        let short_name = name.strip_prefix("refs/tags/").unwrap_or(&name);
+
+        if let Some(version_str) = short_name.strip_prefix(prefix) {
+            if let Ok(version) = Version::parse(version_str) {
+                tags.push((version, oid));
+            }
+        }
+
+        true
+    })?;
+
+    tags.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(tags.pop())
+}
+
+/// Creates an annotated tag `<prefix><version>` pointing at `commit`.
+pub fn create_release_tag(
+    repo: &Repository,
+    prefix: &str,
+    version: &Version,
+    commit: &git2::Commit,
+) -> Result<Oid, TagError> {
+    let signature = repo.signature()?;
+    Ok(repo.tag(
+        &format!("{prefix}{version}"),
+        commit.as_object(),
+        &signature,
+        &format!("Release {version}"),
+        false,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Inits a repo in a tempdir with a single empty commit, returning the
+    /// tempdir (kept alive to keep the repo on disk) alongside the repo.
+    fn init_repo_with_commit() -> (tempfile::TempDir, Repository) {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        drop(tree);
+
+        (dir, repo)
+    }
+
+    #[test]
+    fn test_latest_tagged_version_ignores_tags_without_the_prefix() {
+        let (_dir, repo) = init_repo_with_commit();
+        let commit = repo.head().unwrap().peel_to_commit().unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        repo.tag("other-tag", commit.as_object(), &signature, "unrelated", false)
+            .unwrap();
+
+        assert_eq!(latest_tagged_version(&repo, "v").unwrap(), None);
+    }
+
+    #[test]
+    fn test_latest_tagged_version_picks_the_greatest_version() {
+        let (_dir, repo) = init_repo_with_commit();
+        let commit = repo.head().unwrap().peel_to_commit().unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        for name in ["v1.0.0", "v1.2.0", "v1.1.0"] {
+            repo.tag(name, commit.as_object(), &signature, name, false).unwrap();
+        }
+
+        let (version, oid) = latest_tagged_version(&repo, "v").unwrap().unwrap();
+        assert_eq!(version, Version::parse("1.2.0").unwrap());
+        // `repo.tag` creates an annotated tag object, so `oid` is that
+        // object's id, not the commit's - peel it to compare the target.
+        assert_eq!(repo.find_object(oid, None).unwrap().peel_to_commit().unwrap().id(), commit.id());
+    }
+
+    #[test]
+    fn test_create_release_tag_round_trips_through_latest_tagged_version() {
+        let (_dir, repo) = init_repo_with_commit();
+        let commit = repo.head().unwrap().peel_to_commit().unwrap();
+        let version = Version::parse("2.3.4").unwrap();
+
+        create_release_tag(&repo, "v", &version, &commit).unwrap();
+
+        let (found, oid) = latest_tagged_version(&repo, "v").unwrap().unwrap();
+        assert_eq!(found, version);
+        // Same as above: the found oid is the annotated tag object's id.
+        assert_eq!(repo.find_object(oid, None).unwrap().peel_to_commit().unwrap().id(), commit.id());
+    }
+}
@@ -1,7 +1,10 @@
 use log::*;
-use semver::Version;
+use semver::{BuildMetadata, Prerelease, Version};
 
-use crate::conventional_commit::{ConventionalCommit, ConventionalCommitType};
+use crate::{
+    config::{BumpLevel, BumpLevelTable},
+    conventional_commit::{ConventionalCommit, ConventionalCommitType},
+};
 
 #[derive(Debug, PartialEq)]
 pub struct VersionUpdateTooWeak {
@@ -19,38 +22,94 @@ pub enum ProcessResult {
 pub struct VersionUpdateHandler<'r> {
     pub previous: Version,
     pub current: Version,
-    pub commit: ConventionalCommit<'r>,
+    pub commits: Vec<ConventionalCommit<'r>>,
+    pub bump_levels: BumpLevelTable,
+    /// `Some(label)` attaches/increments a `<label>.N` prerelease instead of
+    /// cutting a final release; `None` cuts (or promotes to) a final release.
+    pub pre_release: Option<String>,
 }
 
 impl<'r> VersionUpdateHandler<'r> {
+    /// Nothing is stable before 1.0: a 0.x crate bumps one level lower
+    /// than a stable one would for the same change (major -> minor,
+    /// minor -> patch); a patch stays a patch.
+    fn discount_for_initial_development(level: BumpLevel) -> BumpLevel {
+        match level {
+            BumpLevel::Major => BumpLevel::Minor,
+            BumpLevel::Minor => BumpLevel::Patch,
+            other => other,
+        }
+    }
+
+    /// `alpha` -> `alpha.1`; if `previous` already carries an `alpha.N`
+    /// prerelease, increments it to `alpha.{N+1}` instead of restarting.
+    fn next_prerelease(previous: &Version, label: &str) -> Prerelease {
+        let next_n = previous
+            .pre
+            .as_str()
+            .strip_prefix(label)
+            .and_then(|rest| rest.strip_prefix('.'))
+            .and_then(|n| n.parse::<u64>().ok())
+            .map_or(1, |n| n + 1);
+
+        Prerelease::new(&format!("{label}.{next_n}"))
+            .expect("label plus a numeric suffix is a valid prerelease identifier")
+    }
+
+    fn bump_level(&self, commit: &ConventionalCommit<'r>) -> BumpLevel {
+        let type_ = ConventionalCommitType::from(commit);
+        trace!("Type of commit: {type_:?}");
+
+        let level = if commit.is_breaking_change {
+            trace!("Breaking Change - Major");
+            BumpLevel::Major
+        } else {
+            let level = self.bump_levels.level_for(&type_);
+            trace!("Type commit {type_:?} maps to {level:?}");
+            level
+        };
+
+        if self.previous.major == 0 {
+            let discounted = Self::discount_for_initial_development(level);
+            trace!("0.x crate - discounting {level:?} to {discounted:?}");
+            discounted
+        } else {
+            level
+        }
+    }
+
     pub fn get_next_version(self) -> Result<ProcessResult, VersionUpdateTooWeak> {
         let mut candidate = self.previous.clone();
+        candidate.pre = Prerelease::EMPTY;
+        candidate.build = BuildMetadata::EMPTY;
 
-        let type_ = ConventionalCommitType::from(&self.commit);
-        trace!("Type of commit: {type_:?}");
+        let bump = self
+            .commits
+            .iter()
+            .map(|commit| self.bump_level(commit))
+            .max()
+            .unwrap_or(BumpLevel::None);
+        trace!("Strongest bump over {} commit(s): {bump:?}", self.commits.len());
 
-        let new_candidate = match type_ {
-            _ if self.commit.is_breaking_change && candidate.major != 0 => {
-                trace!("Breaking Change - Update Major");
-                Version::new(candidate.major + 1, 0, 0)
-            }
-            ConventionalCommitType::Fix => {
-                trace!("Fix without breaking change, update batch");
-                candidate.patch += 1;
-                candidate
-            }
-            ConventionalCommitType::Feat => {
+        let mut new_candidate = match bump {
+            BumpLevel::Major => Version::new(candidate.major + 1, 0, 0),
+            BumpLevel::Minor => {
                 candidate.minor += 1;
-                trace!("New feature, update minor version to {}", candidate.minor);
                 candidate.patch = 0;
                 candidate
             }
-            type_ => {
-                trace!("Type commit {type_:?}, no update version needed");
+            BumpLevel::Patch => {
+                candidate.patch += 1;
                 candidate
             }
+            BumpLevel::None => candidate,
         };
 
+        if let Some(label) = &self.pre_release {
+            new_candidate.pre = Self::next_prerelease(&self.previous, label);
+            trace!("Prerelease requested: {new_candidate}");
+        }
+
         let is_manual_changed = self.current != self.previous;
         if is_manual_changed {
             trace!("Version was manual changed");
@@ -99,7 +158,9 @@ mod tests {
             VersionUpdateHandler {
                 previous: Version::new(1, 2, 3),
                 current: Version::new(1, 2, 3),
-                commit: create_commit("feat", true),
+                commits: vec![create_commit("feat", true)],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: None,
             }
             .get_next_version()
             .unwrap(),
@@ -115,7 +176,9 @@ mod tests {
             VersionUpdateHandler {
                 previous: Version::new(1, 2, 3),
                 current: Version::new(1, 2, 3),
-                commit: create_commit("feat", false),
+                commits: vec![create_commit("feat", false)],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: None,
             }
             .get_next_version()
             .unwrap(),
@@ -131,7 +194,9 @@ mod tests {
             VersionUpdateHandler {
                 previous: Version::new(1, 2, 3),
                 current: Version::new(1, 2, 3),
-                commit: create_commit("fix", false),
+                commits: vec![create_commit("fix", false)],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: None,
             }
             .get_next_version()
             .unwrap(),
@@ -150,7 +215,9 @@ mod tests {
             VersionUpdateHandler {
                 previous: PREVIOUS,
                 current: CURRENT,
-                commit: create_commit("feat", false),
+                commits: vec![create_commit("feat", false)],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: None,
             }
             .get_next_version()
             .unwrap(),
@@ -170,7 +237,9 @@ mod tests {
             VersionUpdateHandler {
                 previous,
                 current: current.clone(),
-                commit: create_commit("feat", false),
+                commits: vec![create_commit("feat", false)],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: None,
             }
             .get_next_version()
             .unwrap_err(),
@@ -180,4 +249,253 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_no_bump_when_no_relevant_commits() {
+        assert_eq!(
+            VersionUpdateHandler {
+                previous: Version::new(1, 2, 3),
+                current: Version::new(1, 2, 3),
+                commits: vec![create_commit("chore", false), create_commit("docs", false)],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: None,
+            }
+            .get_next_version()
+            .unwrap(),
+            ProcessResult::Patch {
+                new: Version::new(1, 2, 3)
+            }
+        );
+    }
+
+    #[test]
+    fn test_strongest_bump_wins_over_the_set() {
+        assert_eq!(
+            VersionUpdateHandler {
+                previous: Version::new(1, 2, 3),
+                current: Version::new(1, 2, 3),
+                commits: vec![
+                    create_commit("fix", false),
+                    create_commit("chore", false),
+                    create_commit("feat", false),
+                ],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: None,
+            }
+            .get_next_version()
+            .unwrap(),
+            ProcessResult::Patch {
+                new: Version::new(1, 3, 0)
+            }
+        );
+    }
+
+    #[test]
+    fn test_initial_development_breaking_change_bumps_minor() {
+        assert_eq!(
+            VersionUpdateHandler {
+                previous: Version::new(0, 3, 4),
+                current: Version::new(0, 3, 4),
+                commits: vec![create_commit("feat", true)],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: None,
+            }
+            .get_next_version()
+            .unwrap(),
+            ProcessResult::Patch {
+                new: Version::new(0, 4, 0)
+            }
+        );
+    }
+
+    #[test]
+    fn test_initial_development_feat_bumps_patch() {
+        assert_eq!(
+            VersionUpdateHandler {
+                previous: Version::new(0, 3, 4),
+                current: Version::new(0, 3, 4),
+                commits: vec![create_commit("feat", false)],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: None,
+            }
+            .get_next_version()
+            .unwrap(),
+            ProcessResult::Patch {
+                new: Version::new(0, 3, 5)
+            }
+        );
+    }
+
+    #[test]
+    fn test_initial_development_fix_bumps_patch() {
+        assert_eq!(
+            VersionUpdateHandler {
+                previous: Version::new(0, 3, 4),
+                current: Version::new(0, 3, 4),
+                commits: vec![create_commit("fix", false)],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: None,
+            }
+            .get_next_version()
+            .unwrap(),
+            ProcessResult::Patch {
+                new: Version::new(0, 3, 5)
+            }
+        );
+    }
+
+    #[test]
+    fn test_stable_breaking_change_bumps_major() {
+        assert_eq!(
+            VersionUpdateHandler {
+                previous: Version::new(1, 3, 4),
+                current: Version::new(1, 3, 4),
+                commits: vec![create_commit("feat", true)],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: None,
+            }
+            .get_next_version()
+            .unwrap(),
+            ProcessResult::Patch {
+                new: Version::new(2, 0, 0)
+            }
+        );
+    }
+
+    #[test]
+    fn test_stable_feat_bumps_minor() {
+        assert_eq!(
+            VersionUpdateHandler {
+                previous: Version::new(1, 3, 4),
+                current: Version::new(1, 3, 4),
+                commits: vec![create_commit("feat", false)],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: None,
+            }
+            .get_next_version()
+            .unwrap(),
+            ProcessResult::Patch {
+                new: Version::new(1, 4, 0)
+            }
+        );
+    }
+
+    #[test]
+    fn test_stable_fix_bumps_patch() {
+        assert_eq!(
+            VersionUpdateHandler {
+                previous: Version::new(1, 3, 4),
+                current: Version::new(1, 3, 4),
+                commits: vec![create_commit("fix", false)],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: None,
+            }
+            .get_next_version()
+            .unwrap(),
+            ProcessResult::Patch {
+                new: Version::new(1, 3, 5)
+            }
+        );
+    }
+
+    #[test]
+    fn test_multiple_breaking_changes_only_bump_major_once() {
+        assert_eq!(
+            VersionUpdateHandler {
+                previous: Version::new(1, 2, 3),
+                current: Version::new(1, 2, 3),
+                commits: vec![
+                    create_commit("feat", true),
+                    create_commit("fix", true),
+                ],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: None,
+            }
+            .get_next_version()
+            .unwrap(),
+            ProcessResult::Patch {
+                new: Version::new(2, 0, 0)
+            }
+        );
+    }
+
+    #[test]
+    fn test_configured_custom_type_triggers_bump() {
+        assert_eq!(
+            VersionUpdateHandler {
+                previous: Version::new(1, 2, 3),
+                current: Version::new(1, 2, 3),
+                commits: vec![create_commit("deps", false)],
+                bump_levels: crate::config::BumpLevelTable::from_toml_value(
+                    "deps = \"minor\"".parse().unwrap()
+                )
+                .unwrap(),
+                pre_release: None,
+            }
+            .get_next_version()
+            .unwrap(),
+            ProcessResult::Patch {
+                new: Version::new(1, 3, 0)
+            }
+        );
+    }
+
+    #[test]
+    fn test_pre_release_starts_at_dot_one() {
+        assert_eq!(
+            VersionUpdateHandler {
+                previous: Version::new(1, 2, 3),
+                current: Version::new(1, 2, 3),
+                commits: vec![create_commit("feat", false)],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: Some("alpha".to_owned()),
+            }
+            .get_next_version()
+            .unwrap(),
+            ProcessResult::Patch {
+                new: Version::parse("1.3.0-alpha.1").unwrap()
+            }
+        );
+    }
+
+    #[test]
+    fn test_pre_release_increments_existing_label() {
+        assert_eq!(
+            VersionUpdateHandler {
+                previous: Version::parse("1.3.0-alpha.1").unwrap(),
+                current: Version::parse("1.3.0-alpha.1").unwrap(),
+                commits: vec![create_commit("chore", false)],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: Some("alpha".to_owned()),
+            }
+            .get_next_version()
+            .unwrap(),
+            ProcessResult::Patch {
+                new: Version::parse("1.3.0-alpha.2").unwrap()
+            }
+        );
+    }
+
+    #[test]
+    fn test_pre_release_promoted_to_final_release() {
+        assert_eq!(
+            VersionUpdateHandler {
+                previous: Version::parse("1.3.0-alpha.2").unwrap(),
+                current: Version::parse("1.3.0-alpha.2").unwrap(),
+                commits: vec![create_commit("chore", false)],
+                bump_levels: BumpLevelTable::default(),
+                pre_release: None,
+            }
+            .get_next_version()
+            .unwrap(),
+            ProcessResult::Patch {
+                new: Version::new(1, 3, 0)
+            }
+        );
+    }
+
+    #[test]
+    fn test_pre_release_version_is_weaker_than_final_release() {
+        assert!(Version::parse("1.3.0-alpha.1").unwrap() < Version::new(1, 3, 0));
+    }
 }